@@ -0,0 +1,99 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/// A single member of an archive, as surfaced to the rest of the crate.
+pub struct ArchiveEntry {
+    pub name: String,
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+/// Archive container formats that `--archive` knows how to peek into.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+/// Detects whether `path` looks like a supported archive: first by its
+/// extension, then, if that's inconclusive, by sniffing its magic bytes.
+pub fn detect_format(path: &Path) -> Option<ArchiveFormat> {
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        return Some(ArchiveFormat::TarGz);
+    }
+    if name.ends_with(".tar") {
+        return Some(ArchiveFormat::Tar);
+    }
+    if name.ends_with(".zip") {
+        return Some(ArchiveFormat::Zip);
+    }
+    sniff_magic_bytes(path)
+}
+
+fn sniff_magic_bytes(path: &Path) -> Option<ArchiveFormat> {
+    let mut f = File::open(path).ok()?;
+    let mut magic = [0u8; 4];
+    f.read_exact(&mut magic).ok()?;
+    if &magic[..2] == b"PK" {
+        return Some(ArchiveFormat::Zip);
+    }
+    if magic[0] == 0x1f && magic[1] == 0x8b {
+        return Some(ArchiveFormat::TarGz);
+    }
+    None
+}
+
+/// Lists an archive's members, optionally restricted to those whose path
+/// starts with `subtree` (so `backup.tar/subdir` only lists `subdir`'s contents).
+pub fn list_entries(path: &Path, format: ArchiveFormat, subtree: Option<&str>) -> Result<Vec<ArchiveEntry>, Box<dyn Error>> {
+    let entries = match format {
+        ArchiveFormat::Tar => list_tar(File::open(path)?)?,
+        ArchiveFormat::TarGz => list_tar(flate2::read::GzDecoder::new(File::open(path)?))?,
+        ArchiveFormat::Zip => list_zip(File::open(path)?)?,
+    };
+
+    Ok(match subtree {
+        // Match on a `/`-terminated prefix (and the prefix itself, for the
+        // subtree's own directory entry) so a prefix like "sub" doesn't also
+        // match a sibling member named "subdir2/...".
+        Some(prefix) => {
+            let prefix_with_slash = format!("{}/", prefix);
+            entries
+                .into_iter()
+                .filter(|e| e.name == prefix || e.name.starts_with(&prefix_with_slash))
+                .collect()
+        }
+        None => entries,
+    })
+}
+
+fn list_tar<R: Read>(reader: R) -> Result<Vec<ArchiveEntry>, Box<dyn Error>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut entries = Vec::new();
+    for entry in archive.entries()? {
+        let entry = entry?;
+        let name = entry.path()?.to_string_lossy().into_owned();
+        let is_dir = entry.header().entry_type().is_dir();
+        let size = entry.header().size()?;
+        entries.push(ArchiveEntry { name, size, is_dir });
+    }
+    Ok(entries)
+}
+
+fn list_zip(file: File) -> Result<Vec<ArchiveEntry>, Box<dyn Error>> {
+    let mut archive = zip::ZipArchive::new(BufReader::new(file))?;
+    let mut entries = Vec::with_capacity(archive.len());
+    for i in 0..archive.len() {
+        let member = archive.by_index(i)?;
+        entries.push(ArchiveEntry {
+            name: member.name().to_string(),
+            size: member.size(),
+            is_dir: member.is_dir(),
+        });
+    }
+    Ok(entries)
+}
@@ -1,96 +1,137 @@
-use clap::{arg, Command, ArgMatches};
-use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
-use std::{fs, io, error};
-use std::io::Write;
-use std::path::PathBuf;
-use std::collections::HashMap;
-
-/* CLI argument parsing via clap crate */
-pub fn args() -> ArgMatches {
-    Command::new("readdir")
-        .version("1.0")
-        .author("benharmonics")
-        .about("Reads items in a given directory")
-        .arg(arg!(-a --all "Show hidden files"))
-        .arg(arg!(-r --reverse "Reverse output order"))
-        .arg(arg!([DIRECTORY] ... "One or more directories to read"))
-        .get_matches()
-}
-
-/* Reads the directory contents and prints them to stdout */
-fn write_to_stdout(stdout: &mut StandardStream, buf: PathBuf, flags: &HashMap<char, bool>)
-                   -> Result<(), Box<dyn error::Error>> {
-    let mut all_entries: Vec<PathBuf> = fs::read_dir(buf.as_path())
-        .unwrap()
-        .map(|res| res.map(|e| e.path()))
-        .collect::<Result<Vec<PathBuf>, io::Error>>()
-        .unwrap_or(vec![]);
-    all_entries.sort();
-
-    // Reverse
-    if flags[&'r'] { all_entries.reverse(); }
-
-    let mut dirs = Vec::new();
-    let mut files = Vec::new();
-
-    for entry in all_entries {
-        // Ignore hidden files
-        if !flags[&'a'] && entry.file_name().unwrap().to_str().unwrap().starts_with('.') { continue; }
-        if entry.is_dir() {
-            dirs.push(entry);
-        } else {
-            files.push(entry);
-        }
-    }
-
-    // Get just the filename/dirname from each PathBuf and collect them into vectors
-    let filenames: Vec<&str> = files.iter()
-        .map(|p| p.file_name().unwrap())
-        .map(|s| s.to_str().unwrap())
-        .collect();
-    let dirnames: Vec<&str> = dirs.iter()
-        .map(|p| p.file_name().unwrap())
-        .map(|s| s.to_str().unwrap())
-        .collect();
-
-    for i in 0..dirs.len() {
-        // Setting the correct color
-        stdout.set_color(ColorSpec::new().set_fg(Some(Color::Blue)).set_bold(true))?;
-        writeln!(&mut *stdout, "{}", dirnames[i])?;
-    }
-    for i in 0..files.len() {
-        // Setting the correct color
-        stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
-        writeln!(&mut *stdout, "{}", filenames[i])?;
-    }
-
-    Ok(())
-}
-
-/* Function is called from main.rs; program exits with an error if anything fails. */
-pub fn run(args: clap::ArgMatches) -> Result<(), Box<dyn error::Error>> {
-    // flags parsed from arguments, normal CLI stuff
-    let flags = HashMap::from([
-        ('a', args.is_present("all")),
-        ('r', args.is_present("reverse")),
-    ]);
-
-    // Set up stdout stream (as opposed to a buffer)
-    let mut stdout = StandardStream::stdout(ColorChoice::Always);
-
-    // If user entered no optional paths to be read, just read the current directory.
-    let dirs: Option<_> = args.values_of("DIRECTORY");
-    if dirs.is_none() {
-        let current_dir = std::env::current_dir()?;
-        write_to_stdout(&mut stdout, current_dir, &flags)?;
-    } else {
-        for dir in dirs.unwrap().collect::<Vec<_>>() {
-            let dir_path = fs::canonicalize(dir)?;
-            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;     // It can change
-            writeln!(&mut stdout, " ==> {} <== ", dir_path.as_os_str().to_str().unwrap())?;
-            write_to_stdout(&mut stdout, dir_path, &flags)?;
-        }
-    }
-
-    Ok(())
-}
+use clap::{arg, ArgAction, Command, ArgMatches};
+use termcolor::{Color, ColorChoice, ColorSpec, StandardStream, WriteColor};
+use std::{fs, error};
+use std::io::Write;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+mod archive;
+mod output;
+
+use output::ListOptions;
+
+/* CLI argument parsing via clap crate */
+pub fn args() -> ArgMatches {
+    Command::new("readdir")
+        .version("1.0")
+        .author("benharmonics")
+        .about("Reads items in a given directory")
+        .arg(arg!(-a --all "Show hidden files"))
+        .arg(arg!(-r --reverse "Reverse output order"))
+        .arg(arg!(-u --unsorted "Leave entries unsorted"))
+        .arg(arg!(-c --"case-sensitive" "Sort case-sensitively"))
+        .arg(arg!(-s --size "Show file sizes"))
+        .arg(arg!(-h --"human-readable" "Show sizes in human-readable form"))
+        .arg(arg!(-b --si "Use powers of 1000 instead of 1024 for human-readable sizes"))
+        .arg(arg!(-l --long "Use a long listing format (permissions, owner, size, mtime)"))
+        .arg(arg!(-t --time "Sort by modification time, newest first"))
+        .arg(arg!(-S --"sort-size" "Sort by size, largest first"))
+        .arg(arg!(-F --classify "Append a type suffix (/, *, @) to each entry"))
+        // Wired to -U rather than -u: -u is already taken by --unsorted above,
+        // so disk usage gets the capitalized letter instead.
+        .arg(arg!(-U --"disk-usage" "Report real allocated disk usage instead of apparent file size"))
+        .arg(arg!(--aggr <N> "With -s, lump entries smaller than N (e.g. 4K, 1M, 2G) into a single summary line").required(false))
+        .arg(arg!(-R --recursive "Recurse into subdirectories, rendering a tree"))
+        .arg(
+            arg!(--depth <N> "Maximum depth to recurse to when using -R")
+                .required(false)
+                .default_value("1"),
+        )
+        .arg(arg!(--ascii "Use ASCII characters instead of box-drawing characters for tree output"))
+        .arg(
+            arg!(-x --exclude <PATTERN> "Exclude entries matching PATTERN (glob); repeatable")
+                .required(false)
+                .action(ArgAction::Append),
+        )
+        .arg(arg!(--archive "Treat .tar/.tar.gz/.zip files as directories and list their contents"))
+        .arg(arg!([DIRECTORY] ... "One or more directories to read"))
+        .get_matches()
+}
+
+/// Given a path like `backup.tar/subdir` that doesn't exist on disk, walks
+/// up its components until it finds one that does, returning that existing
+/// path alongside the remainder as a `/`-joined subtree. Returns `None` if
+/// the path exists as-is, or if no prefix of it exists.
+fn resolve_archive_path(raw: &str) -> Option<(PathBuf, String)> {
+    let mut current = Path::new(raw).to_path_buf();
+    let mut suffix_parts = Vec::new();
+
+    while !current.exists() {
+        let file_name = current.file_name()?.to_str()?.to_string();
+        suffix_parts.push(file_name);
+        if !current.pop() {
+            return None;
+        }
+    }
+
+    if suffix_parts.is_empty() {
+        return None;
+    }
+    suffix_parts.reverse();
+    Some((current, suffix_parts.join("/")))
+}
+
+/* Function is called from main.rs; program exits with an error if anything fails. */
+pub fn run(args: clap::ArgMatches) -> Result<(), Box<dyn error::Error>> {
+    // flags parsed from arguments, normal CLI stuff
+    let flags = HashMap::from([
+        ('a', args.is_present("all")),
+        ('r', args.is_present("reverse")),
+        ('u', args.is_present("unsorted")),
+        ('c', args.is_present("case-sensitive")),
+        ('s', args.is_present("size")),
+        ('h', args.is_present("human-readable")),
+        ('b', args.is_present("si")),
+        ('l', args.is_present("long")),
+        ('t', args.is_present("time")),
+        ('S', args.is_present("sort-size")),
+        ('F', args.is_present("classify")),
+        ('U', args.is_present("disk-usage")),
+        ('R', args.is_present("recursive")),
+    ]);
+
+    let opts = ListOptions {
+        depth: args
+            .value_of("depth")
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(1),
+        ascii: args.is_present("ascii"),
+        aggr_threshold: args.value_of("aggr").map(output::parse_size).transpose()?,
+        exclude: args
+            .values_of("exclude")
+            .map(|vals| vals.map(String::from).collect())
+            .unwrap_or_default(),
+        archive: args.is_present("archive"),
+        archive_subtree: None,
+    };
+
+    // Set up stdout stream (as opposed to a buffer)
+    let mut stdout = StandardStream::stdout(ColorChoice::Always);
+
+    // If user entered no optional paths to be read, just read the current directory.
+    let dirs: Option<_> = args.values_of("DIRECTORY");
+    if dirs.is_none() {
+        let current_dir = std::env::current_dir()?;
+        output::print_entries(&mut stdout, &current_dir, &flags, &opts)?;
+    } else {
+        for dir in dirs.unwrap().collect::<Vec<_>>() {
+            let mut opts = opts.clone();
+            let dir_path = match fs::canonicalize(dir) {
+                Ok(p) => p,
+                Err(e) if opts.archive => match resolve_archive_path(dir) {
+                    Some((archive_path, subtree)) => {
+                        opts.archive_subtree = Some(subtree);
+                        fs::canonicalize(archive_path)?
+                    }
+                    None => return Err(Box::new(e)),
+                },
+                Err(e) => return Err(Box::new(e)),
+            };
+            stdout.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;     // It can change
+            writeln!(&mut stdout, " ==> {} <== ", dir_path.as_os_str().to_str().unwrap())?;
+            output::print_entries(&mut stdout, &dir_path, &flags, &opts)?;
+        }
+    }
+
+    Ok(())
+}
@@ -1,16 +1,176 @@
 use std::{fs, cmp, io, error};
 use std::path::{Path, PathBuf};
 use std::collections::HashMap;
-use std::os::unix::fs::PermissionsExt;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use chrono::{DateTime, Local};
 use termcolor::{WriteColor, ColorSpec, Color};
 use terminal_size::{Width, Height, terminal_size};
+use users::{get_group_by_gid, get_user_by_uid};
+
+/// Settings that carry a value rather than a plain on/off flag.
+///
+/// These don't fit naturally into the `HashMap<char, bool>` that the
+/// boolean CLI flags are collected into, so they're threaded through
+/// separately.
+#[derive(Clone)]
+pub struct ListOptions {
+    /// How many levels deep `-R` is allowed to recurse (default 1, i.e. no descent).
+    pub depth: usize,
+    /// Use ASCII connectors (`|--`, `` `-- ``) instead of box-drawing characters.
+    pub ascii: bool,
+    /// When set alongside `-s`, entries smaller than this many bytes are
+    /// lumped into a single `<aggregated>` summary line instead of being
+    /// printed individually. Only the default grid listing does this; `-l`
+    /// and `-R` list every entry.
+    pub aggr_threshold: Option<u64>,
+    /// Glob patterns from `-x`/`--exclude`; entries whose file name matches
+    /// any of these are filtered out before sorting and display.
+    pub exclude: Vec<String>,
+    /// Whether `--archive` was passed, enabling `.tar`/`.tar.gz`/`.zip` files
+    /// to be listed as if they were directories.
+    pub archive: bool,
+    /// For paths like `backup.tar/subdir`, the subtree inside the archive to list.
+    pub archive_subtree: Option<String>,
+}
+
+impl Default for ListOptions {
+    fn default() -> Self {
+        ListOptions {
+            depth: 1,
+            ascii: false,
+            aggr_threshold: None,
+            exclude: Vec::new(),
+            archive: false,
+            archive_subtree: None,
+        }
+    }
+}
+
+/// Matches `text` against a shell-style glob pattern (`*` for any run of
+/// characters, `?` for exactly one). `pattern` is taken pre-compiled (its
+/// chars already collected) so callers matching it against many entries
+/// don't redo that work on every comparison.
+fn glob_match(pattern: &[char], text: &str) -> bool {
+    fn helper(pattern: &[char], text: &[char]) -> bool {
+        match pattern.first() {
+            None => text.is_empty(),
+            Some('*') => helper(&pattern[1..], text) || (!text.is_empty() && helper(pattern, &text[1..])),
+            Some('?') => !text.is_empty() && helper(&pattern[1..], &text[1..]),
+            Some(c) => !text.is_empty() && text[0] == *c && helper(&pattern[1..], &text[1..]),
+        }
+    }
+    let text: Vec<char> = text.chars().collect();
+    helper(pattern, &text)
+}
+
+/// Parses a size threshold like `4K`, `1M` or `2G` (binary units, i.e. `K`
+/// means 1024) into a byte count. A bare number is taken as bytes.
+pub(crate) fn parse_size(s: &str) -> Result<u64, Box<dyn error::Error>> {
+    let s = s.trim();
+    let (digits, multiplier) = match s.chars().last() {
+        Some('K') | Some('k') => (&s[..s.len() - 1], 1024),
+        Some('M') | Some('m') => (&s[..s.len() - 1], 1024 * 1024),
+        Some('G') | Some('g') => (&s[..s.len() - 1], 1024 * 1024 * 1024),
+        _ => (s, 1),
+    };
+    let n: u64 = digits.trim().parse()?;
+    Ok(n * multiplier)
+}
+
+/// The real on-disk size of `entry` in bytes (in `-U` mode, its allocated
+/// 512-byte blocks times 512) or its apparent length, or `None` if its
+/// metadata can't be read.
+/// Reuses `cached` (from `collect_sorted_entries`'s `-t`/`-S` stat) instead
+/// of re-stat'ing when it's available.
+fn entry_size(entry: &Path, flags: &HashMap<char, bool>, cached: Option<&fs::Metadata>) -> Option<u64> {
+    let stat_result;
+    let attrs = match cached {
+        Some(attrs) => attrs,
+        None => {
+            stat_result = entry.metadata().ok()?;
+            &stat_result
+        }
+    };
+    if flags[&'U'] {
+        Some(attrs.blocks() * 512)
+    } else {
+        Some(attrs.len())
+    }
+}
+
+/// Splits entries smaller than `threshold` out of `entries` when `-s` and
+/// `--aggr` are both active, returning the remaining entries plus the
+/// combined size and count of whatever was split out.
+fn partition_for_aggregation(
+    entries: Vec<CachedEntry>,
+    flags: &HashMap<char, bool>,
+    threshold: Option<u64>,
+) -> (Vec<CachedEntry>, Option<(u64, usize)>) {
+    let threshold = match threshold {
+        Some(t) if flags[&'s'] => t,
+        _ => return (entries, None),
+    };
+
+    let mut kept = Vec::new();
+    let mut sum = 0u64;
+    let mut count = 0usize;
+    for (p, cached) in entries {
+        match entry_size(&p, flags, cached.as_ref()) {
+            Some(size) if size < threshold => {
+                sum += size;
+                count += 1;
+            }
+            _ => kept.push((p, cached)),
+        }
+    }
+
+    let aggregated = if count > 0 { Some((sum, count)) } else { None };
+    (kept, aggregated)
+}
 
 /// Prints all the items in a directory to stdout
 pub fn print_entries<W: WriteColor>(
-    buffer: &mut W, 
-    path: &Path, 
+    buffer: &mut W,
+    path: &Path,
     flags: &HashMap<char, bool>,
+    opts: &ListOptions,
 ) -> Result<(), Box<dyn error::Error>> {
+    if opts.archive {
+        if let Some(format) = crate::archive::detect_format(path) {
+            let archive_entries = crate::archive::list_entries(path, format, opts.archive_subtree.as_deref())?;
+            print_archive_entries(buffer, &archive_entries, flags)?;
+            writeln!(buffer)?;
+            return Ok(());
+        }
+    }
+
+    if flags[&'R'] {
+        print_tree(buffer, path, flags, opts, 1, "")?;
+    } else if flags[&'l'] {
+        let entries = collect_sorted_entries(path, flags, &opts.exclude)?;
+        print_long_format(buffer, &entries, flags)?;
+    } else {
+        let entries = collect_sorted_entries(path, flags, &opts.exclude)?;
+        let (entries, aggregated) = partition_for_aggregation(entries, flags, opts.aggr_threshold);
+        write_dir_contents_to_buffer(buffer, &entries, flags, aggregated)?;
+    }
+    // A last newline for formatting
+    writeln!(buffer)?;
+
+    Ok(())
+}
+
+/// An entry plus the `fs::Metadata` fetched to sort it by `-t`/`-S`, so the
+/// print path can reuse it instead of re-stat'ing. `None` when no sort mode
+/// that needs metadata was requested, or when the stat failed.
+type CachedEntry = (PathBuf, Option<fs::Metadata>);
+
+/// Reads a directory's immediate children, applying the hidden-file filter,
+/// the `-x`/`--exclude` patterns, and the `-a`/`-c`/`-u`/`-r`/`-t`/`-S`
+/// sort/reverse logic shared by every listing mode. Excluding a directory
+/// also keeps the recursive tree mode from descending into it, since it's
+/// simply absent from the entries it has to recurse over.
+fn collect_sorted_entries(path: &Path, flags: &HashMap<char, bool>, exclude: &[String]) -> Result<Vec<CachedEntry>, Box<dyn error::Error>> {
     let mut pathbufs: Vec<PathBuf> = fs::read_dir(path)?
         .map(|res| res.map(|e| e.path()))
         .collect::<Result<Vec<PathBuf>, _>>()?;
@@ -20,44 +180,254 @@ pub fn print_entries<W: WriteColor>(
         pathbufs.retain(|e| !e.file_name().unwrap().to_str().unwrap().starts_with('.'));
     }
 
-    // Leave items unsorted if -u flag was used
-    if !flags[&'u'] {
-        if flags[&'c'] {
-            pathbufs.sort();    // case-sensitive sort by default
+    if !exclude.is_empty() {
+        // Compile each pattern's chars once up front instead of re-collecting
+        // them from the source string on every entry compared against it.
+        let patterns: Vec<Vec<char>> = exclude.iter().map(|p| p.chars().collect()).collect();
+        pathbufs.retain(|e| {
+            let name = e.file_name().unwrap().to_str().unwrap_or("");
+            !patterns.iter().any(|pattern| glob_match(pattern, name))
+        });
+    }
+
+    // -t (newest first) and -S (largest first) need metadata to sort by, so
+    // we cache it alongside each path and hand it on to the print path
+    // instead of re-stat'ing there.
+    let mut entries: Vec<CachedEntry> = if flags[&'t'] || flags[&'S'] {
+        let mut entries: Vec<CachedEntry> = pathbufs
+            .into_iter()
+            .map(|p| {
+                let metadata = p.metadata().ok();
+                (p, metadata)
+            })
+            .collect();
+
+        if flags[&'t'] {
+            entries.sort_by(|(_, a), (_, b)| {
+                let a = a.as_ref().and_then(|m| m.modified().ok());
+                let b = b.as_ref().and_then(|m| m.modified().ok());
+                // Entries whose metadata couldn't be read sort last, deterministically.
+                match (a, b) {
+                    (Some(a), Some(b)) => b.cmp(&a),    // newest first
+                    (Some(_), None) => cmp::Ordering::Less,
+                    (None, Some(_)) => cmp::Ordering::Greater,
+                    (None, None) => cmp::Ordering::Equal,
+                }
+            });
         } else {
-            pathbufs.sort_by(|a, b| {   // case-insensitive sorting
-                a
-                    .as_path()
-                    .to_str()
-                    .unwrap_or("")
-                    .to_lowercase()
-                    .partial_cmp(&b.as_path().to_str().unwrap_or("").to_lowercase())
-                    .unwrap()
+            entries.sort_by(|(_, a), (_, b)| {
+                let a = a.as_ref().map(|m| m.len());
+                let b = b.as_ref().map(|m| m.len());
+                match (a, b) {
+                    (Some(a), Some(b)) => b.cmp(&a),    // largest first
+                    (Some(_), None) => cmp::Ordering::Less,
+                    (None, Some(_)) => cmp::Ordering::Greater,
+                    (None, None) => cmp::Ordering::Equal,
+                }
             });
         }
-    }
+
+        entries
+    } else {
+        // Leave items unsorted if -u flag was used
+        if !flags[&'u'] {
+            if flags[&'c'] {
+                pathbufs.sort();    // case-sensitive sort by default
+            } else {
+                pathbufs.sort_by(|a, b| {   // case-insensitive sorting
+                    a
+                        .as_path()
+                        .to_str()
+                        .unwrap_or("")
+                        .to_lowercase()
+                        .partial_cmp(&b.as_path().to_str().unwrap_or("").to_lowercase())
+                        .unwrap()
+                });
+            }
+        }
+        pathbufs.into_iter().map(|p| (p, None)).collect()
+    };
 
     // Reverse the items if -r flag was used
     if flags[&'r'] {
-        pathbufs.reverse();
+        entries.reverse();
     }
-    
-    // A Vec of Paths from which we'll write to the buffer
-    let entries: Vec<&Path> = pathbufs.iter().map(|b| b.as_path()).collect();
 
-    // Writing to the buffer
-    write_dir_contents_to_buffer(buffer, entries, flags)?;
-    // A last newline for formatting
-    writeln!(buffer, "")?;
+    Ok(entries)
+}
+
+/// Recursively renders `dir`'s contents as a tree, descending into
+/// subdirectories until `opts.depth` is reached. `current_depth` is the
+/// depth of the entries about to be printed (the directory's immediate
+/// children are depth 1). Symlinked directories are never descended into,
+/// so cyclic symlinks can't cause infinite recursion.
+fn print_tree<W: WriteColor>(
+    buffer: &mut W,
+    dir: &Path,
+    flags: &HashMap<char, bool>,
+    opts: &ListOptions,
+    current_depth: usize,
+    prefix: &str,
+) -> Result<(), Box<dyn error::Error>> {
+    let entries = collect_sorted_entries(dir, flags, &opts.exclude)?;
+
+    for (i, (entry, cached)) in entries.iter().enumerate() {
+        let is_last = i == entries.len() - 1;
+        let connector = if opts.ascii {
+            if is_last { "`-- " } else { "|-- " }
+        } else if is_last {
+            "└── "
+        } else {
+            "├── "
+        };
+        let filename = entry.file_name().unwrap_or(std::ffi::OsStr::new("")).to_str().unwrap_or("");
+
+        // Reset to the default color before drawing the connector, so it
+        // doesn't inherit whatever color the previous entry's name left set.
+        buffer.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+        write!(buffer, "{}{}", prefix, connector)?;
+        set_entry_color(buffer, entry, cached.as_ref())?;
+        writeln!(buffer, "{}", filename)?;
+
+        if entry.is_dir() && !entry.is_symlink() && current_depth < opts.depth {
+            // Connectors ("├── "/"└── ") are 4 columns wide, so the
+            // continuation prefix needs to be 4 columns too or deeper
+            // levels drift left of the names above them.
+            let child_prefix = if is_last {
+                format!("{}    ", prefix)
+            } else if opts.ascii {
+                format!("{}|   ", prefix)
+            } else {
+                format!("{}│   ", prefix)
+            };
+            print_tree(buffer, entry, flags, opts, current_depth + 1, &child_prefix)?;
+        }
+    }
+
+    buffer.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+    Ok(())
+}
+
+/// Sets the buffer's color to match the entry's type, following the same
+/// scheme as [`write_dir_contents_to_buffer`]: broken entries are red,
+/// directories blue, symlinks cyan, and files with any execute bit set green.
+/// Reuses `cached` instead of re-stat'ing when it's available.
+fn set_entry_color<W: WriteColor>(buffer: &mut W, entry: &Path, cached: Option<&fs::Metadata>) -> io::Result<()> {
+    if !entry.exists() {
+        return buffer.set_color(ColorSpec::new().set_fg(Some(Color::Red)).set_bold(true));
+    }
+
+    let mut spec = ColorSpec::new();
+    spec.set_fg(Some(Color::White));
+    if entry.is_dir() {
+        spec.set_fg(Some(Color::Blue)).set_bold(true);
+    }
+    if entry.is_symlink() {
+        spec.set_fg(Some(Color::Cyan));
+    }
+    let mode = match cached {
+        Some(attrs) => Some(attrs.permissions().mode()),
+        None => entry.metadata().ok().map(|m| m.permissions().mode()),
+    };
+    if let Some(mode) = mode {
+        if mode & 0o111 != 0 && !entry.is_dir() {
+            spec.set_fg(Some(Color::Green));
+        }
+    }
+    buffer.set_color(&spec)
+}
+
+/// Returns the `-F` classification suffix for an entry: `/` for directories,
+/// `*` for files with any execute bit set, `@` for symlinks (broken ones
+/// included), or an empty string for anything else (including when `-F`
+/// wasn't requested).
+fn classify_suffix(entry: &Path, flags: &HashMap<char, bool>) -> &'static str {
+    if !flags[&'F'] {
+        return "";
+    }
+    // Check is_symlink() first: is_dir() follows symlinks, so a symlink
+    // pointing at a directory would otherwise be classified as "/" instead
+    // of "@".
+    if entry.is_symlink() {
+        "@"
+    } else if entry.is_dir() {
+        "/"
+    } else if entry.metadata().map(|m| m.permissions().mode() & 0o111 != 0).unwrap_or(false) {
+        "*"
+    } else {
+        ""
+    }
+}
+
+/// Renders an archive's members using the same grid/size layout as a
+/// regular directory listing, colored cyan like symlinks to set them apart.
+fn print_archive_entries<W: WriteColor>(
+    buffer: &mut W,
+    entries: &[crate::archive::ArchiveEntry],
+    flags: &HashMap<char, bool>,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut cyan = ColorSpec::new();
+    cyan.set_fg(Some(Color::Cyan));
+
+    // Archive formats don't reliably bake a trailing "/" into directory
+    // member names, so append one here using the is_dir flag they do report.
+    let display_name = |entry: &crate::archive::ArchiveEntry| -> String {
+        if entry.is_dir && !entry.name.ends_with('/') {
+            format!("{}/", entry.name)
+        } else {
+            entry.name.clone()
+        }
+    };
+
+    let length_of_longest_entry: usize = entries.iter().map(|e| display_name(e).len()).max().unwrap_or(0);
+    let console_width: usize = console_width();
+    let buffer_width: usize = cmp::min(length_of_longest_entry + 2, console_width);
+    let entries_per_line: usize = cmp::max(console_width / buffer_width, 1);
+
+    for (i, entry) in entries.iter().enumerate() {
+        buffer.set_color(&cyan)?;
+
+        let name = display_name(entry);
+        let outstr = if buffer_width * entries.len() <= console_width {
+            right_pad(&name, name.len() + 2)
+        } else {
+            right_pad(&name, buffer_width)
+        };
+
+        if flags[&'s'] {
+            let size_str = if !flags[&'h'] {
+                format!("{} B", entry.size)
+            } else {
+                human_readable_filesize(entry.size, flags[&'b'])?
+            };
+            write!(buffer, "{}", right_pad(&size_str, 10))?;
+            if i < entries.len() - 1 {
+                writeln!(buffer, "{}", outstr)?;
+            } else {
+                write!(buffer, "{}", outstr)?;
+            }
+            continue;
+        }
+
+        if i % entries_per_line == entries_per_line - 1 && i != entries.len() - 1 {
+            writeln!(buffer, "{}", outstr)?;
+        } else {
+            write!(buffer, "{}", outstr)?;
+        }
+    }
+    buffer.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
 
     Ok(())
 }
 
-/// Reads data from Paths & writes to buffer to be flushed later
+/// Reads data from entries & writes to buffer to be flushed later. Reuses
+/// each entry's cached `fs::Metadata` (from `collect_sorted_entries`'s
+/// `-t`/`-S` stat) instead of re-stat'ing it here when it's available.
 fn write_dir_contents_to_buffer<W: WriteColor>(
-    buffer: &mut W, 
-    entries: Vec<&Path>, 
+    buffer: &mut W,
+    entries: &[CachedEntry],
     flags: &HashMap<char, bool>,
+    aggregated: Option<(u64, usize)>,
 ) -> Result<(), Box<dyn error::Error>> {
     // I'm not sure if it's efficient to specify colors up front here...
     let mut blue = ColorSpec::new();
@@ -71,23 +441,28 @@ fn write_dir_contents_to_buffer<W: WriteColor>(
     white.set_fg(Some(Color::White));
     red.set_fg(Some(Color::Red)).set_bold(true);
 
-    // Note that both uses of unwrap can never fail here because each entry 
+    // Note that both uses of unwrap can never fail here because each entry
     // has already been converted to a PathBuf
     let length_of_longest_entry: usize = entries.iter()
-        .map(|&e| e.file_name().unwrap().to_str().unwrap().len())
+        .map(|(e, _)| e.file_name().unwrap().to_str().unwrap().len() + classify_suffix(e, flags).len())
         .max()
         .unwrap_or(0);
     let console_width: usize = console_width();
     let buffer_width: usize = cmp::min(length_of_longest_entry + 2, console_width);
     let entries_per_line: usize = cmp::max(console_width / buffer_width, 1);
 
-    for (i, entry) in entries.iter().enumerate() {
-        // File name
-        let filename: &str = entry.file_name().unwrap_or(std::ffi::OsStr::new("")).to_str().unwrap_or("");
+    for (i, (entry, cached)) in entries.iter().enumerate() {
+        // File name, plus the -F classification suffix if requested
+        let filename: String = format!(
+            "{}{}",
+            entry.file_name().unwrap_or(std::ffi::OsStr::new("")).to_str().unwrap_or(""),
+            classify_suffix(entry, flags),
+        );
+        let filename: &str = &filename;
         // Handle missing files / broken symlinks
         if !entry.exists() {
             buffer.set_color(&red)?;
-            if i % entries_per_line == entries_per_line - 1 
+            if i % entries_per_line == entries_per_line - 1
                 && i != entries.len() - 1
             {
                 writeln!(buffer, "{}", right_pad(filename, buffer_width))?;
@@ -97,9 +472,12 @@ fn write_dir_contents_to_buffer<W: WriteColor>(
             continue;
         }
 
-        // File metadata
-        let attrs: fs::Metadata = entry.metadata()?;
-        
+        // File metadata, reusing the cached stat when we have one
+        let attrs: fs::Metadata = match cached {
+            Some(attrs) => attrs.clone(),
+            None => entry.metadata()?,
+        };
+
         // Setting font colors
         buffer.set_color(&white)?;
         if entry.is_dir() {
@@ -107,7 +485,7 @@ fn write_dir_contents_to_buffer<W: WriteColor>(
         }
         if entry.is_symlink() {
             buffer.set_color(&cyan)?;
-        } 
+        }
         if attrs.permissions().mode() & 0o111 != 0
             && !entry.is_dir()
         {
@@ -123,12 +501,14 @@ fn write_dir_contents_to_buffer<W: WriteColor>(
 
         // Printing out the file size throws off the whole formatting scheme, so it's a separate thing here.
         if flags[&'s'] {
-            let file_size = if !flags[&'h'] { 
-                format!("{} B", attrs.len())
-            } else if flags[&'b'] { 
-                human_readable_filesize(attrs.len(), true)?
+            // Real allocated disk usage (-U) instead of apparent length
+            let size = if flags[&'U'] { attrs.blocks() * 512 } else { attrs.len() };
+            let file_size = if !flags[&'h'] {
+                format!("{} B", size)
+            } else if flags[&'b'] {
+                human_readable_filesize(size, true)?
             } else {
-                human_readable_filesize(attrs.len(), false)?
+                human_readable_filesize(size, false)?
             };
             write!(buffer, "{}", right_pad(&file_size, 10))?;
             if i < entries.len() - 1 {
@@ -150,6 +530,23 @@ fn write_dir_contents_to_buffer<W: WriteColor>(
     }
     buffer.set_color(&white)?;  // Revert colors just in case - not useless
 
+    // Small entries lumped together by --aggr get a summary line of their own
+    if let Some((sum, count)) = aggregated {
+        if !entries.is_empty() {
+            writeln!(buffer)?;
+        }
+        let size_str = if !flags[&'h'] {
+            format!("{} B", sum)
+        } else if flags[&'b'] {
+            human_readable_filesize(sum, true)?
+        } else {
+            human_readable_filesize(sum, false)?
+        };
+        buffer.set_color(&white)?;
+        write!(buffer, "{}", right_pad(&size_str, 10))?;
+        writeln!(buffer, "<aggregated> ({} entries)", count)?;
+    }
+
     Ok(())
 }
 
@@ -178,6 +575,118 @@ fn right_pad(s: &str, width: usize) -> String {
     res
 }
 
+/// Pad a string with spaces on the left side
+fn left_pad(s: &str, width: usize) -> String {
+    let mut res = String::new();
+    while res.len() + s.len() < width {
+        res.push(' ');
+    }
+    res.push_str(s);
+    res
+}
+
+/// Renders a `drwxr-xr-x`-style permission string from a raw mode and the
+/// entry's type.
+fn permission_string(mode: u32, is_dir: bool, is_symlink: bool) -> String {
+    const RWX: [(u32, char); 9] = [
+        (0o400, 'r'), (0o200, 'w'), (0o100, 'x'),
+        (0o040, 'r'), (0o020, 'w'), (0o010, 'x'),
+        (0o004, 'r'), (0o002, 'w'), (0o001, 'x'),
+    ];
+    let file_type = if is_symlink { 'l' } else if is_dir { 'd' } else { '-' };
+    let mut s = String::with_capacity(10);
+    s.push(file_type);
+    for (mask, ch) in RWX {
+        s.push(if mode & mask != 0 { ch } else { '-' });
+    }
+    s
+}
+
+/// Resolves a uid to a username, falling back to the raw number if it can't be looked up.
+fn user_name(uid: u32) -> String {
+    get_user_by_uid(uid)
+        .map(|u| u.name().to_string_lossy().into_owned())
+        .unwrap_or_else(|| uid.to_string())
+}
+
+/// Resolves a gid to a group name, falling back to the raw number if it can't be looked up.
+fn group_name(gid: u32) -> String {
+    get_group_by_gid(gid)
+        .map(|g| g.name().to_string_lossy().into_owned())
+        .unwrap_or_else(|| gid.to_string())
+}
+
+/// One row of `-l` long-format output.
+struct LongRow<'a> {
+    path: &'a Path,
+    perm: String,
+    nlink: u64,
+    owner: String,
+    group: String,
+    size: String,
+    mtime: String,
+    filename: String,
+}
+
+/// Prints entries in `-l` long-listing format: permissions, link count,
+/// owner/group, size, and modification time, one entry per line with
+/// columns aligned to the widest value in each field.
+fn print_long_format<W: WriteColor>(
+    buffer: &mut W,
+    entries: &[CachedEntry],
+    flags: &HashMap<char, bool>,
+) -> Result<(), Box<dyn error::Error>> {
+    let mut rows = Vec::with_capacity(entries.len());
+    for (entry, _) in entries {
+        // -l always reports the entry itself rather than a symlink's target,
+        // so this uses symlink_metadata regardless of the -t/-S cache (which
+        // was populated with the target-following metadata() instead).
+        let attrs = fs::symlink_metadata(entry)?;
+        let mode = attrs.permissions().mode();
+        let filename = entry.file_name().unwrap_or(std::ffi::OsStr::new("")).to_str().unwrap_or("").to_string();
+        let size = if flags[&'h'] {
+            human_readable_filesize(attrs.len(), flags[&'b'])?
+        } else {
+            attrs.len().to_string()
+        };
+        let mtime = DateTime::<Local>::from(attrs.modified()?).format("%b %e %H:%M").to_string();
+
+        rows.push(LongRow {
+            path: entry.as_path(),
+            perm: permission_string(mode, attrs.is_dir(), attrs.file_type().is_symlink()),
+            nlink: attrs.nlink(),
+            owner: user_name(attrs.uid()),
+            group: group_name(attrs.gid()),
+            size,
+            mtime,
+            filename,
+        });
+    }
+
+    let nlink_width = rows.iter().map(|r| r.nlink.to_string().len()).max().unwrap_or(0);
+    let owner_width = rows.iter().map(|r| r.owner.len()).max().unwrap_or(0);
+    let group_width = rows.iter().map(|r| r.group.len()).max().unwrap_or(0);
+    let size_width = rows.iter().map(|r| r.size.len()).max().unwrap_or(0);
+
+    for row in &rows {
+        write!(
+            buffer,
+            "{} {} {} {} {} {} ",
+            row.perm,
+            left_pad(&row.nlink.to_string(), nlink_width),
+            right_pad(&row.owner, owner_width),
+            right_pad(&row.group, group_width),
+            left_pad(&row.size, size_width),
+            row.mtime,
+        )?;
+        set_entry_color(buffer, row.path, None)?;
+        writeln!(buffer, "{}", row.filename)?;
+    }
+    buffer.set_color(ColorSpec::new().set_fg(Some(Color::White)))?;
+
+    Ok(())
+}
+
 /// Prints file sizes like 4.14 kB, 2.1 GB, etc.
 fn human_readable_filesize(num: u64, base_1000: bool) -> Result<String, Box<dyn error::Error>> {
     let units = ["B", "kB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"];